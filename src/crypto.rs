@@ -6,10 +6,17 @@ use crate::Error;
 use crate::Result;
 use cipher_spec::AES_256_GCM;
 
+mod aes_gcm_siv_crypter;
 mod cipher_spec;
+#[cfg(not(feature = "pure-rust-crypto"))]
 mod hkdf;
+#[cfg(not(feature = "pure-rust-crypto"))]
 mod key_type;
+#[cfg(not(feature = "pure-rust-crypto"))]
 mod openssl_crypter;
+#[cfg(feature = "pure-rust-crypto")]
+mod rust_crypto_crypter;
+#[cfg(not(feature = "pure-rust-crypto"))]
 mod sodium_crypter;
 
 // Crypto-related modules as described in https://shadowsocks.org/en/spec/AEAD-Ciphers.
@@ -34,22 +41,111 @@ pub fn create_crypter(
     key_bytes: &[u8],
     nonce_type: NonceType,
     cipher_type: CipherType,
-) -> Box<dyn Crypter> {
+) -> Result<Box<dyn Crypter>> {
+    let spec = cipher_type.spec();
+    spec.check_key(key_bytes)?;
+
+    // AES-256-GCM-SIV has no OpenSSL binding, so it's backed by a pure-Rust crate regardless of
+    // the `pure-rust-crypto` feature.
+    if cipher_type == CipherType::Aes256GcmSiv {
+        if spec.nonce_size != aes_gcm_siv_crypter::Aes256GcmSivCrypter::NONCE_BYTES
+        {
+            return Err(Error::NonceSize);
+        }
+        let crypter = aes_gcm_siv_crypter::Aes256GcmSivCrypter::create_crypter(
+            key_bytes, nonce_type,
+        );
+        return Ok(Box::new(crypter));
+    }
+
+    #[cfg(feature = "pure-rust-crypto")]
+    return create_crypter_pure_rust(key_bytes, nonce_type, cipher_type);
+
+    #[cfg(not(feature = "pure-rust-crypto"))]
+    return create_crypter_openssl_sodium(key_bytes, nonce_type, cipher_type);
+}
+
+// Backed by RustCrypto crates, so creating a crypter needs no system OpenSSL or libsodium.
+// `derive_master_key_compatible` is not covered by the feature: it always calls OpenSSL.
+#[cfg(feature = "pure-rust-crypto")]
+fn create_crypter_pure_rust(
+    key_bytes: &[u8],
+    nonce_type: NonceType,
+    cipher_type: CipherType,
+) -> Result<Box<dyn Crypter>> {
+    let spec = cipher_type.spec();
+
+    macro_rules! check_and_create {
+        ($crypter:ty) => {{
+            if spec.key_size != <$crypter>::KEY_BYTES {
+                return Err(Error::KeySize);
+            }
+            if spec.nonce_size != <$crypter>::NONCE_BYTES {
+                return Err(Error::NonceSize);
+            }
+            Box::new(<$crypter>::create_crypter(key_bytes, nonce_type))
+        }};
+    }
+
+    let crypter: Box<dyn Crypter> = match cipher_type {
+        CipherType::Aes128GCM => {
+            check_and_create!(rust_crypto_crypter::RustCryptoAes128GcmCrypter)
+        }
+        CipherType::Aes192GCM => {
+            check_and_create!(rust_crypto_crypter::RustCryptoAes192GcmCrypter)
+        }
+        CipherType::Aes256GCM => {
+            check_and_create!(rust_crypto_crypter::RustCryptoAes256GcmCrypter)
+        }
+        CipherType::Chacha20IetfPoly1305 => {
+            check_and_create!(
+                rust_crypto_crypter::RustCryptoChacha20Poly1305Crypter
+            )
+        }
+        CipherType::XChacha20IetfPoly1305 => {
+            check_and_create!(
+                rust_crypto_crypter::RustCryptoXChacha20Poly1305Crypter
+            )
+        }
+        CipherType::Aes256GcmSiv => unreachable!("handled by the caller"),
+        #[cfg(test)]
+        CipherType::None => unreachable!("CipherType::None is a test-only placeholder"),
+    };
+    Ok(crypter)
+}
+
+#[cfg(not(feature = "pure-rust-crypto"))]
+fn create_crypter_openssl_sodium(
+    key_bytes: &[u8],
+    nonce_type: NonceType,
+    cipher_type: CipherType,
+) -> Result<Box<dyn Crypter>> {
+    let spec = cipher_type.spec();
+
     if cipher_type == CipherType::Chacha20IetfPoly1305 {
+        if spec.nonce_size
+            != sodium_crypter::Chacha20IetfPoly1305Crypter::NONCE_BYTES
+        {
+            return Err(Error::NonceSize);
+        }
         let crypter =
             sodium_crypter::Chacha20IetfPoly1305Crypter::create_crypter(
                 key_bytes, nonce_type,
             );
-        let spec = cipher_type.spec();
-        assert_eq!(
-            spec.key_size,
-            sodium_crypter::Chacha20IetfPoly1305Crypter::KEY_BYTES
-        );
-        assert_eq!(
-            spec.nonce_size,
-            sodium_crypter::Chacha20IetfPoly1305Crypter::NONCE_BYTES
-        );
-        return Box::new(crypter);
+        return Ok(Box::new(crypter));
+    }
+
+    if cipher_type == CipherType::XChacha20IetfPoly1305 {
+        if spec.nonce_size
+            != sodium_crypter::XChacha20IetfPoly1305Crypter::NONCE_BYTES
+        {
+            return Err(Error::NonceSize);
+        }
+        let crypter =
+            sodium_crypter::XChacha20IetfPoly1305Crypter::create_crypter(
+                key_bytes, nonce_type,
+            );
+        return Ok(Box::new(crypter));
     }
 
     let cipher = match cipher_type {
@@ -65,14 +161,17 @@ pub fn create_crypter(
         }
     };
 
-    let spec = cipher_type.spec();
-    assert_eq!(spec.key_size, cipher.key_len());
+    if spec.key_size != cipher.key_len() {
+        return Err(Error::KeySize);
+    }
     // An iv_len of None indicates that the cipher does not support IV.
-    assert_eq!(spec.nonce_size, cipher.iv_len().unwrap_or(0));
+    if spec.nonce_size != cipher.iv_len().unwrap_or(0) {
+        return Err(Error::NonceSize);
+    }
 
-    Box::new(openssl_crypter::OpensslCrypter::create(
+    Ok(Box::new(openssl_crypter::OpensslCrypter::create(
         cipher, key_bytes, nonce_type,
-    ))
+    )))
 }
 
 // Recommended way of deriving a key from a password. Incompatible with the method used in the
@@ -81,7 +180,7 @@ pub fn create_crypter(
 // OpenSSL and BoringSSL.
 const RECOMMENDED_ITERATION_COUNT: u32 = 1000; // Iteration count recommended by RFC2898.
 
-#[cfg(feature = "ring-crypto")]
+#[cfg(all(feature = "ring-crypto", not(feature = "pure-rust-crypto")))]
 pub fn derive_master_key_pbkdf2(
     password: &[u8],
     salt: &[u8],
@@ -99,7 +198,23 @@ pub fn derive_master_key_pbkdf2(
     Ok(buf)
 }
 
-#[cfg(not(feature = "ring-crypto"))]
+#[cfg(feature = "pure-rust-crypto")]
+pub fn derive_master_key_pbkdf2(
+    password: &[u8],
+    salt: &[u8],
+    key_size: usize,
+) -> Result<Vec<u8>> {
+    let mut buf = vec![0u8; key_size];
+    pbkdf2::pbkdf2_hmac::<sha2::Sha256>(
+        password,
+        salt,
+        RECOMMENDED_ITERATION_COUNT,
+        buf.as_mut_slice(),
+    );
+    Ok(buf)
+}
+
+#[cfg(not(any(feature = "ring-crypto", feature = "pure-rust-crypto")))]
 pub fn derive_master_key_pbkdf2(
     password: &[u8],
     salt: &[u8],
@@ -122,6 +237,68 @@ pub fn derive_master_key_pbkdf2(
     }
 }
 
+// Memory-hard key derivation, recommended over `derive_master_key_pbkdf2` when the master
+// password may be weak, since scrypt's tunable memory cost makes large-scale GPU/ASIC cracking
+// far more expensive than a plain iterated hash.
+//
+// `N`, `r` and `p` must be agreed upon by both peers ahead of time (they live in the server
+// config, not on the wire) so that the same password always derives the same key.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ScryptParams {
+    // Base-2 logarithm of the CPU/memory cost parameter `N`, so `N` = 2^log_n.
+    pub log_n: u8,
+    // Block size parameter.
+    pub r: u32,
+    // Parallelization parameter.
+    pub p: u32,
+}
+
+impl ScryptParams {
+    // Suitable for interactive use, e.g. a client prompting for a password at connect time.
+    // Mirrors libsodium pwhash's `OPSLIMIT_INTERACTIVE`/`MEMLIMIT_INTERACTIVE` preset.
+    pub const INTERACTIVE: ScryptParams =
+        ScryptParams { log_n: 15, r: 8, p: 1 };
+
+    // Suitable for long-lived secrets where the extra derivation cost is paid once, e.g. a
+    // server config loaded at startup. Mirrors libsodium pwhash's `OPSLIMIT_SENSITIVE` preset.
+    pub const SENSITIVE: ScryptParams =
+        ScryptParams { log_n: 20, r: 8, p: 1 };
+}
+
+impl Default for ScryptParams {
+    fn default() -> Self {
+        ScryptParams::INTERACTIVE
+    }
+}
+
+pub fn derive_master_key_scrypt(
+    password: &[u8],
+    salt: &[u8],
+    key_size: usize,
+    params: ScryptParams,
+) -> Result<Vec<u8>> {
+    let longest_key_size: usize = AES_256_GCM.key_size;
+    if key_size > longest_key_size {
+        log::error!("Cannot derive a key longer than {}", longest_key_size);
+        return Err(Error::KeySize);
+    }
+
+    let scrypt_params =
+        scrypt::Params::new(params.log_n, params.r, params.p, key_size)
+            .map_err(|e| {
+                log::error!("Invalid scrypt parameters {}", e);
+                Error::KeyDerivationError
+            })?;
+
+    let mut buf = vec![0u8; key_size];
+    scrypt::scrypt(password, salt, &scrypt_params, buf.as_mut_slice())
+        .map_err(|e| {
+            log::error!("Error deriving key {}", e);
+            Error::KeyDerivationError
+        })?;
+    Ok(buf)
+}
+
 // The key derivation method used by the original Shadowsocks Python version.
 // The derived key should be identical to the one generated by the Python version. The derived IV is
 // different from the Python version. Fortunately IV is used as salt and set by the party that
@@ -158,7 +335,7 @@ pub fn derive_master_key_compatible(
 
 const SHADOW_INFO: &'static [u8] = b"ss-subkey";
 
-#[cfg(feature = "ring-crypto")]
+#[cfg(all(feature = "ring-crypto", not(feature = "pure-rust-crypto")))]
 fn derive_subkey_with_algorithm(
     master_key: &[u8],
     salt: &[u8],
@@ -181,7 +358,29 @@ fn derive_subkey_with_algorithm(
     ret
 }
 
-#[cfg(not(feature = "ring-crypto"))]
+// Routed to the `hkdf`/`sha2`/`sha1` RustCrypto crates so this function has no C dependencies,
+// like the crypters and `derive_master_key_pbkdf2` under `pure-rust-crypto`.
+#[cfg(feature = "pure-rust-crypto")]
+fn derive_subkey_with_algorithm(
+    master_key: &[u8],
+    salt: &[u8],
+    key_size: usize,
+    use_sha1: bool,
+) -> Vec<u8> {
+    let mut ret = vec![0u8; key_size];
+    if use_sha1 {
+        let hk = ::hkdf::Hkdf::<sha1::Sha1>::new(Some(salt), master_key);
+        hk.expand(SHADOW_INFO, &mut ret)
+            .expect("Should not expand key to too long");
+    } else {
+        let hk = ::hkdf::Hkdf::<sha2::Sha256>::new(Some(salt), master_key);
+        hk.expand(SHADOW_INFO, &mut ret)
+            .expect("Should not expand key to too long");
+    }
+    ret
+}
+
+#[cfg(not(any(feature = "ring-crypto", feature = "pure-rust-crypto")))]
 fn derive_subkey_with_algorithm(
     master_key: &[u8],
     salt: &[u8],
@@ -224,7 +423,8 @@ pub fn derive_subkey(
 #[rustfmt::skip::macros(crypto_array, crypto_vec)]
 mod test {
     use crate::crypto::cipher_spec::{
-        AES_128_GCM, AES_192_GCM, CHACHA20_IETF_POLY1305,
+        AES_128_GCM, AES_192_GCM, AES_256_GCM_SIV, CHACHA20_IETF_POLY1305,
+        XCHACHA20_IETF_POLY1305,
     };
 
     use super::*;
@@ -289,6 +489,40 @@ mod test {
         Ok(())
     }
 
+    // Expected key is the first 32 bytes of RFC 7914's scrypt("", "", N=16, r=1, p=1, dkLen=64)
+    // test vector. Keys longer than 32 bytes are rejected, and scrypt's final PBKDF2-HMAC-SHA256
+    // step makes a 32-byte output a prefix of the 64-byte one.
+    #[test]
+    fn test_derive_master_key_scrypt_known_vector() -> Result<()> {
+        let key = derive_master_key_scrypt(
+            b"",
+            b"",
+            32,
+            ScryptParams { log_n: 4, r: 1, p: 1 },
+        )?;
+        assert_eq!(
+            key,
+            &crypto_array![
+                0x77, 0xD6, 0x57, 0x62, 0x38, 0x65, 0x7B, 0x20,
+                0x3B, 0x19, 0xCA, 0x42, 0xC1, 0x8A, 0x04, 0x97,
+                0xF1, 0x6B, 0x48, 0x44, 0xE3, 0x07, 0x4A, 0xE8,
+                0xDF, 0xDF, 0xFA, 0x3F, 0xED, 0xE2, 0x14, 0x42
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_derive_master_key_scrypt_rejects_oversized_key() {
+        let result = derive_master_key_scrypt(
+            b"password",
+            b"salt",
+            AES_256_GCM.key_size + 1,
+            ScryptParams::default(),
+        );
+        assert!(matches!(result, Err(Error::KeySize)));
+    }
+
     #[test]
     fn test_master_key_derivation_compatibility_short_keys() -> Result<()> {
         let key = derive_master_key_compatible(b"deadbeef", 24)?;
@@ -389,6 +623,7 @@ mod test {
             &AES_128_GCM,
             &AES_192_GCM,
             &AES_256_GCM,
+            &AES_256_GCM_SIV,
             &CHACHA20_IETF_POLY1305,
         ] {
             assert_eq!(spec.key_size, spec.salt_size);
@@ -396,4 +631,120 @@ mod test {
             assert_eq!(spec.tag_size, TAG_BYTES);
         }
     }
+
+    #[test]
+    fn test_aes_256_gcm_siv_round_trip() -> Result<()> {
+        let key = vec![0x42u8; AES_256_GCM_SIV.key_size];
+        let mut encryptor = create_crypter(
+            &key,
+            NonceType::Sequential,
+            CipherType::Aes256GcmSiv,
+        )?;
+        let mut decryptor = create_crypter(
+            &key,
+            NonceType::Sequential,
+            CipherType::Aes256GcmSiv,
+        )?;
+
+        let plaintext = b"hello aes-256-gcm-siv";
+        let ciphertext = encryptor.encrypt(plaintext)?;
+        assert_eq!(
+            ciphertext.len(),
+            encryptor.expected_ciphertext_length(plaintext.len())
+        );
+        assert_eq!(decryptor.decrypt(&ciphertext)?, plaintext);
+        Ok(())
+    }
+
+    // Only runs when built with `--features pure-rust-crypto`; exercises the RustCrypto-backed
+    // dispatch path in `create_crypter_pure_rust` instead of the OpenSSL/libsodium one.
+    #[test]
+    #[cfg(feature = "pure-rust-crypto")]
+    fn test_pure_rust_crypto_aes_256_gcm_round_trip() -> Result<()> {
+        let key = vec![0x24u8; AES_256_GCM.key_size];
+        let mut encryptor = create_crypter(
+            &key,
+            NonceType::Sequential,
+            CipherType::Aes256GCM,
+        )?;
+        let mut decryptor = create_crypter(
+            &key,
+            NonceType::Sequential,
+            CipherType::Aes256GCM,
+        )?;
+
+        let plaintext = b"pure rust crypto round trip";
+        let ciphertext = encryptor.encrypt(plaintext)?;
+        assert_eq!(decryptor.decrypt(&ciphertext)?, plaintext);
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_crypter_rejects_wrong_key_length() {
+        let key = vec![0u8; AES_256_GCM.key_size - 1];
+        let result =
+            create_crypter(&key, NonceType::Sequential, CipherType::Aes256GCM);
+        assert!(matches!(result, Err(Error::KeySize)));
+    }
+
+    #[test]
+    fn test_decrypt_rejects_input_shorter_than_tag() -> Result<()> {
+        let key = vec![0x11u8; AES_256_GCM_SIV.key_size];
+        let mut decryptor = create_crypter(
+            &key,
+            NonceType::Sequential,
+            CipherType::Aes256GcmSiv,
+        )?;
+        let result = decryptor.decrypt(&[0u8; 4]);
+        assert!(matches!(result, Err(Error::Length)));
+        Ok(())
+    }
+
+    // `create_crypter` returns `Error::NonceSize` if a cipher's declared `CipherSpec` ever
+    // disagreed with its backend crypter's `NONCE_BYTES`; that can't be triggered through the
+    // public API since the two tables are always kept in sync by construction, so this test
+    // guards the invariant directly instead.
+    #[test]
+    fn test_cipher_spec_nonce_sizes_match_backend_constants() {
+        assert_eq!(
+            AES_256_GCM_SIV.nonce_size,
+            aes_gcm_siv_crypter::Aes256GcmSivCrypter::NONCE_BYTES
+        );
+        assert_eq!(
+            AES_256_GCM_SIV.key_size,
+            aes_gcm_siv_crypter::Aes256GcmSivCrypter::KEY_BYTES
+        );
+    }
+
+    // XChaCha20-Ietf-Poly1305's 24-byte nonce is intentionally excluded from
+    // `test_size_assumptions`'s shared 12-byte `NONCE_BYTES` check; assert it here instead.
+    #[test]
+    fn test_xchacha20_ietf_poly1305_nonce_size() {
+        assert_eq!(XCHACHA20_IETF_POLY1305.nonce_size, 24);
+        assert_eq!(XCHACHA20_IETF_POLY1305.tag_size, TAG_BYTES);
+    }
+
+    #[test]
+    fn test_xchacha20_ietf_poly1305_round_trip() -> Result<()> {
+        let key = vec![0x77u8; XCHACHA20_IETF_POLY1305.key_size];
+        let mut encryptor = create_crypter(
+            &key,
+            NonceType::Sequential,
+            CipherType::XChacha20IetfPoly1305,
+        )?;
+        let mut decryptor = create_crypter(
+            &key,
+            NonceType::Sequential,
+            CipherType::XChacha20IetfPoly1305,
+        )?;
+
+        let plaintext = b"hello xchacha20-ietf-poly1305";
+        let ciphertext = encryptor.encrypt(plaintext)?;
+        assert_eq!(
+            ciphertext.len(),
+            encryptor.expected_ciphertext_length(plaintext.len())
+        );
+        assert_eq!(decryptor.decrypt(&ciphertext)?, plaintext);
+        Ok(())
+    }
 }
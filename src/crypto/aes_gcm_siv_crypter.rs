@@ -0,0 +1,70 @@
+// AES-256-GCM-SIV is not exposed by OpenSSL's `symm::Cipher`, so unlike the other AEAD ciphers
+// this one is backed directly by the RustCrypto `aes-gcm-siv` crate.
+
+use aes_gcm_siv::aead::{Aead, KeyInit};
+use aes_gcm_siv::{Aes256GcmSiv, Nonce};
+
+use super::{Crypter, NonceType, TAG_BYTES};
+use crate::Error;
+use crate::Result;
+
+pub struct Aes256GcmSivCrypter {
+    cipher: Aes256GcmSiv,
+    nonce: [u8; Self::NONCE_BYTES],
+    nonce_type: NonceType,
+}
+
+impl Aes256GcmSivCrypter {
+    pub const KEY_BYTES: usize = 32;
+    pub const NONCE_BYTES: usize = 12;
+
+    pub fn create_crypter(key_bytes: &[u8], nonce_type: NonceType) -> Self {
+        let cipher = Aes256GcmSiv::new_from_slice(key_bytes)
+            .expect("key_bytes should have been validated by the caller");
+        Aes256GcmSivCrypter {
+            cipher,
+            nonce: [0u8; Self::NONCE_BYTES],
+            nonce_type,
+        }
+    }
+
+    fn advance_nonce(&mut self) {
+        if let NonceType::Sequential = self.nonce_type {
+            for byte in self.nonce.iter_mut() {
+                *byte = byte.wrapping_add(1);
+                if *byte != 0 {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl Crypter for Aes256GcmSivCrypter {
+    fn encrypt(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        let nonce = Nonce::from_slice(&self.nonce);
+        let ret = self.cipher.encrypt(nonce, data).map_err(|e| {
+            log::error!("Error encrypting with aes-256-gcm-siv: {}", e);
+            Error::EncryptionError
+        })?;
+        self.advance_nonce();
+        Ok(ret)
+    }
+
+    fn decrypt(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < TAG_BYTES {
+            return Err(Error::Length);
+        }
+        let nonce = Nonce::from_slice(&self.nonce);
+        let ret = self.cipher.decrypt(nonce, data).map_err(|e| {
+            log::error!("Error decrypting with aes-256-gcm-siv: {}", e);
+            Error::DecryptionError
+        })?;
+        self.advance_nonce();
+        Ok(ret)
+    }
+
+    fn expected_ciphertext_length(&self, plaintext_length: usize) -> usize {
+        plaintext_length + TAG_BYTES
+    }
+}
@@ -0,0 +1,88 @@
+// AES-GCM backend built on OpenSSL's `symm` module.
+
+use openssl::symm::Cipher;
+
+use super::{Crypter, NonceType, TAG_BYTES};
+use crate::Error;
+use crate::Result;
+
+pub struct OpensslCrypter {
+    cipher: Cipher,
+    key: Vec<u8>,
+    nonce: Vec<u8>,
+    nonce_type: NonceType,
+}
+
+impl OpensslCrypter {
+    pub fn create(
+        cipher: Cipher,
+        key_bytes: &[u8],
+        nonce_type: NonceType,
+    ) -> Self {
+        let nonce = vec![0u8; cipher.iv_len().unwrap_or(0)];
+        OpensslCrypter {
+            cipher,
+            key: key_bytes.to_vec(),
+            nonce,
+            nonce_type,
+        }
+    }
+
+    fn advance_nonce(&mut self) {
+        if let NonceType::Sequential = self.nonce_type {
+            for byte in self.nonce.iter_mut() {
+                *byte = byte.wrapping_add(1);
+                if *byte != 0 {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl Crypter for OpensslCrypter {
+    fn encrypt(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut tag = [0u8; TAG_BYTES];
+        let ciphertext = openssl::symm::encrypt_aead(
+            self.cipher,
+            &self.key,
+            Some(&self.nonce),
+            &[],
+            data,
+            &mut tag,
+        )
+        .map_err(|e| {
+            log::error!("Error encrypting: {}", e);
+            Error::EncryptionError
+        })?;
+        self.advance_nonce();
+        let mut ret = ciphertext;
+        ret.extend_from_slice(&tag);
+        Ok(ret)
+    }
+
+    fn decrypt(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < TAG_BYTES {
+            return Err(Error::Length);
+        }
+        let (ciphertext, tag) = data.split_at(data.len() - TAG_BYTES);
+        let ret = openssl::symm::decrypt_aead(
+            self.cipher,
+            &self.key,
+            Some(&self.nonce),
+            &[],
+            ciphertext,
+            tag,
+        )
+        .map_err(|e| {
+            log::error!("Error decrypting: {}", e);
+            Error::DecryptionError
+        })?;
+        self.advance_nonce();
+        Ok(ret)
+    }
+
+    fn expected_ciphertext_length(&self, plaintext_length: usize) -> usize {
+        plaintext_length + TAG_BYTES
+    }
+}
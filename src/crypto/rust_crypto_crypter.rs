@@ -0,0 +1,88 @@
+// Pure-Rust AEAD backends selected by the `pure-rust-crypto` feature, used in place of
+// `openssl_crypter`/`sodium_crypter` so the crypters need no system OpenSSL or libsodium.
+// Each crypter keeps the same ciphertext-followed-by-tag output and
+// `NonceType::Sequential`/`Zero` nonce handling as the other `Crypter` implementations.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{AesGcm, Key};
+use aes_gcm::aes::{Aes128, Aes192, Aes256};
+use aes_gcm::aead::consts::U12;
+use chacha20poly1305::{ChaCha20Poly1305, XChaCha20Poly1305};
+use generic_array::GenericArray;
+
+use super::{Crypter, NonceType, TAG_BYTES};
+use crate::Error;
+use crate::Result;
+
+type Aes192Gcm = AesGcm<Aes192, U12>;
+
+macro_rules! impl_rust_crypto_crypter {
+    ($name:ident, $cipher:ty, $key_bytes:expr, $nonce_bytes:expr) => {
+        pub struct $name {
+            cipher: $cipher,
+            nonce: [u8; $nonce_bytes],
+            nonce_type: NonceType,
+        }
+
+        impl $name {
+            pub const KEY_BYTES: usize = $key_bytes;
+            pub const NONCE_BYTES: usize = $nonce_bytes;
+
+            pub fn create_crypter(key_bytes: &[u8], nonce_type: NonceType) -> Self {
+                let key = Key::<$cipher>::from_slice(key_bytes);
+                let cipher = <$cipher>::new(key);
+                $name {
+                    cipher,
+                    nonce: [0u8; $nonce_bytes],
+                    nonce_type,
+                }
+            }
+
+            fn advance_nonce(&mut self) {
+                if let NonceType::Sequential = self.nonce_type {
+                    for byte in self.nonce.iter_mut() {
+                        *byte = byte.wrapping_add(1);
+                        if *byte != 0 {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        impl Crypter for $name {
+            fn encrypt(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+                let nonce = GenericArray::from_slice(&self.nonce);
+                let ret = self.cipher.encrypt(nonce, data).map_err(|e| {
+                    log::error!("Error encrypting: {}", e);
+                    Error::EncryptionError
+                })?;
+                self.advance_nonce();
+                Ok(ret)
+            }
+
+            fn decrypt(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+                if data.len() < TAG_BYTES {
+                    return Err(Error::Length);
+                }
+                let nonce = GenericArray::from_slice(&self.nonce);
+                let ret = self.cipher.decrypt(nonce, data).map_err(|e| {
+                    log::error!("Error decrypting: {}", e);
+                    Error::DecryptionError
+                })?;
+                self.advance_nonce();
+                Ok(ret)
+            }
+
+            fn expected_ciphertext_length(&self, plaintext_length: usize) -> usize {
+                plaintext_length + TAG_BYTES
+            }
+        }
+    };
+}
+
+impl_rust_crypto_crypter!(RustCryptoAes128GcmCrypter, AesGcm<Aes128, U12>, 16, 12);
+impl_rust_crypto_crypter!(RustCryptoAes192GcmCrypter, Aes192Gcm, 24, 12);
+impl_rust_crypto_crypter!(RustCryptoAes256GcmCrypter, AesGcm<Aes256, U12>, 32, 12);
+impl_rust_crypto_crypter!(RustCryptoChacha20Poly1305Crypter, ChaCha20Poly1305, 32, 12);
+impl_rust_crypto_crypter!(RustCryptoXChacha20Poly1305Crypter, XChaCha20Poly1305, 32, 24);
@@ -0,0 +1,37 @@
+use std::fmt;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    // A supplied key's length doesn't match what the cipher or KDF expects.
+    KeySize,
+    // A cipher's declared nonce size doesn't match what its backend crypter expects.
+    NonceSize,
+    // A ciphertext is too short to contain its authentication tag.
+    Length,
+    // Deriving a master or sub key from a password/salt failed.
+    KeyDerivationError,
+    // Encrypting a plaintext with the selected cipher failed.
+    EncryptionError,
+    // Decrypting a ciphertext with the selected cipher failed (e.g. authentication failure).
+    DecryptionError,
+    // The cipher name given on the command line or in a config file isn't recognized.
+    UnknownCipher(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::KeySize => write!(f, "invalid key size"),
+            Error::NonceSize => write!(f, "invalid nonce size"),
+            Error::Length => write!(f, "input too short"),
+            Error::KeyDerivationError => write!(f, "key derivation failed"),
+            Error::EncryptionError => write!(f, "encryption failed"),
+            Error::DecryptionError => write!(f, "decryption failed"),
+            Error::UnknownCipher(name) => write!(f, "unknown cipher: {}", name),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
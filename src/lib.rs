@@ -0,0 +1,5 @@
+mod crypto;
+mod error;
+
+pub use error::Error;
+pub use error::Result;